@@ -8,7 +8,7 @@
 //! - restore(persisted_state) -> state
 //! - observe() -> observation
 
-use nomercy_core::{simulate, EngineConfig, SimulationSystem};
+use nomercy_core::{simulate, EngineConfig, FaultPolicy, SimulationSystem};
 use serde::{Deserialize, Serialize};
 
 /// Configuration for the Flake system.
@@ -102,11 +102,23 @@ pub fn run_flake_schedule(
     seed: u64,
     budget: Option<u64>,
     operations: &[FlakeOp],
+) -> nomercy_core::SimulationOutcome<Flake> {
+    run_flake_schedule_with_faults(seed, budget, FaultPolicy::Never, operations)
+}
+
+/// Like [`run_flake_schedule`], but lets the caller opt into mid-run
+/// crash/restore fault injection via `fault_policy`.
+pub fn run_flake_schedule_with_faults(
+    seed: u64,
+    budget: Option<u64>,
+    fault_policy: FaultPolicy,
+    operations: &[FlakeOp],
 ) -> nomercy_core::SimulationOutcome<Flake> {
     let config = EngineConfig {
         seed,
         budget,
         system_config: FlakeConfig::default(),
+        fault_policy,
     };
 
     simulate::<Flake>(config, operations)
@@ -1,4 +1,6 @@
-use flake::{run_flake_schedule, FlakeOp, FlakeState};
+use flake::{run_flake_schedule, run_flake_schedule_with_faults, FlakeOp, FlakeState};
+use nomercy_core::engine::{SimulationStatus, SimulationStep};
+use nomercy_core::{simulate, EngineConfig, FaultPolicy, SimulationSystem};
 
 #[test]
 fn crash_restore_round_trip_is_deterministic() {
@@ -14,7 +16,7 @@ fn crash_restore_round_trip_is_deterministic() {
         outcome
             .steps
             .iter()
-            .map(|step| step.observation.counter)
+            .map(|step| step.observation().counter)
             .collect::<Vec<_>>(),
         vec![2, 1, 5]
     );
@@ -31,3 +33,93 @@ fn crash_restore_round_trip_is_deterministic() {
         expected_state.journal
     );
 }
+
+#[test]
+fn probabilistic_fault_policy_injects_and_is_reproducible() {
+    let operations = vec![
+        FlakeOp::Increment(1),
+        FlakeOp::Increment(1),
+        FlakeOp::Increment(1),
+        FlakeOp::Increment(1),
+    ];
+    let policy = FaultPolicy::Probabilistic { probability: 0.9 };
+
+    let outcome_a = run_flake_schedule_with_faults(7, None, policy.clone(), &operations);
+    let outcome_b = run_flake_schedule_with_faults(7, None, policy, &operations);
+
+    let injected = outcome_a
+        .steps
+        .iter()
+        .filter(|step| matches!(step, SimulationStep::FaultInjected { .. }))
+        .count();
+    assert!(injected > 0, "expected at least one fault to be injected");
+
+    assert_eq!(format!("{outcome_a:?}"), format!("{outcome_b:?}"));
+}
+
+/// A lossy counter whose `crash` persists one less than the live counter,
+/// so a restore loses the most recent `+1` write, standing in
+/// for a system with a real persistence bug — used to prove
+/// `PostRestoreDivergence` is actually reachable and reported correctly.
+struct LossyCounter {
+    counter: i64,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct LossyCounterObservation {
+    counter: i64,
+}
+
+#[derive(Clone, Debug)]
+struct LossyCounterState {
+    counter: i64,
+}
+
+impl SimulationSystem for LossyCounter {
+    type Config = ();
+    type Operation = i64;
+    type Observation = LossyCounterObservation;
+    type PersistedState = LossyCounterState;
+
+    fn init(_config: Self::Config) -> Self {
+        LossyCounter { counter: 0 }
+    }
+
+    fn apply(&mut self, op: Self::Operation) {
+        self.counter += op;
+    }
+
+    fn crash(self) -> Self::PersistedState {
+        LossyCounterState {
+            counter: self.counter.saturating_sub(1),
+        }
+    }
+
+    fn restore(state: Self::PersistedState) -> Self {
+        LossyCounter {
+            counter: state.counter,
+        }
+    }
+
+    fn observe(&self) -> Self::Observation {
+        LossyCounterObservation {
+            counter: self.counter,
+        }
+    }
+}
+
+#[test]
+fn post_restore_divergence_is_detected_when_restore_loses_a_write() {
+    let config = EngineConfig {
+        seed: 1,
+        budget: None,
+        system_config: (),
+        fault_policy: FaultPolicy::Probabilistic { probability: 1.0 },
+    };
+    let outcome = simulate::<LossyCounter>(config, &[1, 1, 1]);
+
+    match outcome.status {
+        SimulationStatus::PostRestoreDivergence { at_step, .. } => assert_eq!(at_step, 0),
+        SimulationStatus::Completed => panic!("expected a post-restore divergence"),
+    }
+}
@@ -0,0 +1,150 @@
+//! Demonstrates the payoff of dependency-tracked invariant re-evaluation
+//! (`Dirty`/`Operation::with_writes`/`Invariant::with_reads`) on a model
+//! wide enough that most invariants are unaffected by any one operation.
+//!
+//! Run with `cargo bench -p nomercy-core`. The two benchmark functions
+//! time the same schedule with and without declared reads/writes; the
+//! `eprintln!` each prints how many invariant evaluations a single run
+//! performed, which is the number the dependency tracking is meant to cut.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use nomercy_core::prelude::{Invariant, Operation, Severity, Simulation, SystemModel};
+
+const FIELDS: [&str; 8] = [
+    "e0", "e1", "e2", "e3", "e4", "e5", "e6", "e7",
+];
+/// A 1-element dependency set per field, declared once so `with_writes`
+/// and `with_reads` (which both need a `&'static [&'static str]`) have a
+/// `'static` slice to borrow instead of one scoped to a loop iteration.
+const DEPENDENCY_SETS: [&[&str]; 8] = [
+    &["e0"], &["e1"], &["e2"], &["e3"], &["e4"], &["e5"], &["e6"], &["e7"],
+];
+const INVARIANTS_PER_FIELD: usize = 16;
+const ROUNDS: usize = 50;
+
+static EVALUATIONS: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Clone, Default)]
+struct WideState {
+    e0: i64,
+    e1: i64,
+    e2: i64,
+    e3: i64,
+    e4: i64,
+    e5: i64,
+    e6: i64,
+    e7: i64,
+}
+
+#[derive(Clone, Default)]
+struct WideObservation {
+    e0: i64,
+    e1: i64,
+    e2: i64,
+    e3: i64,
+    e4: i64,
+    e5: i64,
+    e6: i64,
+    e7: i64,
+}
+
+fn observe(state: &WideState) -> WideObservation {
+    WideObservation {
+        e0: state.e0,
+        e1: state.e1,
+        e2: state.e2,
+        e3: state.e3,
+        e4: state.e4,
+        e5: state.e5,
+        e6: state.e6,
+        e7: state.e7,
+    }
+}
+
+macro_rules! field_ops {
+    ($bump:ident, $check:ident, $field:ident) => {
+        fn $bump(state: &mut WideState) {
+            state.$field += 1;
+        }
+
+        fn $check(observation: &WideObservation) -> Option<String> {
+            EVALUATIONS.fetch_add(1, Ordering::Relaxed);
+            if observation.$field >= 0 {
+                None
+            } else {
+                Some(format!("{} went negative", stringify!($field)))
+            }
+        }
+    };
+}
+
+field_ops!(bump_e0, check_e0, e0);
+field_ops!(bump_e1, check_e1, e1);
+field_ops!(bump_e2, check_e2, e2);
+field_ops!(bump_e3, check_e3, e3);
+field_ops!(bump_e4, check_e4, e4);
+field_ops!(bump_e5, check_e5, e5);
+field_ops!(bump_e6, check_e6, e6);
+field_ops!(bump_e7, check_e7, e7);
+
+const BUMPS: [fn(&mut WideState); 8] = [
+    bump_e0, bump_e1, bump_e2, bump_e3, bump_e4, bump_e5, bump_e6, bump_e7,
+];
+const CHECKS: [fn(&WideObservation) -> Option<String>; 8] = [
+    check_e0, check_e1, check_e2, check_e3, check_e4, check_e5, check_e6, check_e7,
+];
+
+/// A model with one operation per field and `INVARIANTS_PER_FIELD`
+/// invariants per field, so a single applied operation is, in principle,
+/// relevant to only `1/FIELDS.len()` of the invariants in the model.
+fn build_model(track_dependencies: bool) -> SystemModel<WideState, WideObservation> {
+    let mut model = SystemModel::new("wide", WideState::default).with_observer(observe);
+
+    for ((field, bump), dependencies) in FIELDS.into_iter().zip(BUMPS).zip(DEPENDENCY_SETS) {
+        let mut operation = Operation::new(field, bump);
+        if track_dependencies {
+            operation = operation.with_writes(dependencies);
+        }
+        model = model.operation(operation);
+    }
+
+    for ((field, check), dependencies) in FIELDS.into_iter().zip(CHECKS).zip(DEPENDENCY_SETS) {
+        for _ in 0..INVARIANTS_PER_FIELD {
+            let mut invariant = Invariant::new(field, Severity::Error, check);
+            if track_dependencies {
+                invariant = invariant.with_reads(dependencies);
+            }
+            model = model.invariant(invariant);
+        }
+    }
+
+    model
+}
+
+fn report_evaluations(label: &str, simulation: &Simulation<WideState, WideObservation>) {
+    EVALUATIONS.store(0, Ordering::Relaxed);
+    simulation.run(1);
+    let total = EVALUATIONS.load(Ordering::Relaxed);
+    eprintln!("{label}: {total} invariant evaluations for one round ({} ops)", FIELDS.len());
+}
+
+fn bench_untracked(c: &mut Criterion) {
+    let simulation = Simulation::new(build_model(false));
+    report_evaluations("without dependency tracking", &simulation);
+    c.bench_function("wide_model/without_dependency_tracking", |b| {
+        b.iter(|| simulation.run(ROUNDS));
+    });
+}
+
+fn bench_tracked(c: &mut Criterion) {
+    let simulation = Simulation::new(build_model(true));
+    report_evaluations("with dependency tracking", &simulation);
+    c.bench_function("wide_model/with_dependency_tracking", |b| {
+        b.iter(|| simulation.run(ROUNDS));
+    });
+}
+
+criterion_group!(benches, bench_untracked, bench_tracked);
+criterion_main!(benches);
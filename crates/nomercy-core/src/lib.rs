@@ -1,57 +1,709 @@
 pub mod prelude {
-    pub use crate::invariant::{Invariant, InvariantResult};
+    pub use crate::invariant::{Dirty, Invariant, InvariantResult, Severity};
     pub use crate::operation::Operation;
+    pub use crate::repro::Repro;
     pub use crate::simulation::{Simulation, SimulationOutcome, SimulationStatus, SimulationStep};
     pub use crate::system::{Observation, SystemModel};
     pub use nomercy_macros::{invariant, observe, op, system};
 }
 
+pub use engine::{simulate, EngineConfig, FaultPolicy, SimulationSystem};
+pub use shrink::ddmin;
+
+pub mod trace {
+    //! Structured tracing over the simulation loop. The core instrumentation
+    //! here only depends on the `tracing` crate (one span per run, with
+    //! nested events per step; there is no separate span per round), so
+    //! embedding the engine stays dependency-light. The
+    //! live console and the NDJSON file layer pull in `tracing-subscriber`
+    //! and are gated behind the `console` feature.
+
+    use tracing::{debug, info, span, Level};
+
+    /// One span per run, tagging every nested step/invariant/crash event
+    /// with the seed that drove the scheduler.
+    pub fn run_span(system: &str, seed: u64) -> tracing::Span {
+        span!(Level::INFO, "simulation_run", system, seed)
+    }
+
+    pub fn op_applied(step_index: usize, op_index: usize, op: &str) {
+        info!(step_index, op_index, op, "operation applied");
+    }
+
+    pub fn invariant_checked(invariant: &str, severity: &str, holds: bool) {
+        debug!(invariant, severity, holds, "invariant checkpoint");
+    }
+
+    pub fn fault_injected(step_index: usize) {
+        info!(step_index, "crash/restore injected");
+    }
+
+    pub fn outcome_completed(system: &str) {
+        info!(system, "simulation completed");
+    }
+
+    pub fn outcome_invariant_violated(system: &str, failure_count: usize) {
+        info!(system, failure_count, "simulation invariant violated");
+    }
+
+    #[cfg(feature = "console")]
+    pub mod console {
+        //! A live per-run console in the spirit of `tokio-console`: step
+        //! count, steps/sec, and invariant pass/fail counts, rendered from
+        //! the same events the core engine emits. It does not show which
+        //! operations the schedule has chosen.
+
+        use std::sync::atomic::{AtomicU64, Ordering};
+        use std::sync::Arc;
+        use std::time::Instant;
+
+        use tracing::field::{Field, Visit};
+        use tracing_subscriber::layer::Context;
+        use tracing_subscriber::Layer;
+
+        #[derive(Default)]
+        struct Counts {
+            steps: AtomicU64,
+            invariant_passes: AtomicU64,
+            invariant_failures: AtomicU64,
+        }
+
+        /// A `tracing_subscriber::Layer` that tallies engine events into
+        /// live counters a console view can poll and render.
+        pub struct ConsoleLayer {
+            counts: Arc<Counts>,
+            started: Instant,
+        }
+
+        impl ConsoleLayer {
+            pub fn new() -> Self {
+                Self {
+                    counts: Arc::new(Counts::default()),
+                    started: Instant::now(),
+                }
+            }
+
+            pub fn steps_per_second(&self) -> f64 {
+                let steps = self.counts.steps.load(Ordering::Relaxed) as f64;
+                let elapsed = self.started.elapsed().as_secs_f64().max(f64::EPSILON);
+                steps / elapsed
+            }
+
+            /// A single-line snapshot suitable for redrawing in place.
+            pub fn render(&self) -> String {
+                format!(
+                    "steps={} steps/sec={:.1} invariants(pass={}, fail={})",
+                    self.counts.steps.load(Ordering::Relaxed),
+                    self.steps_per_second(),
+                    self.counts.invariant_passes.load(Ordering::Relaxed),
+                    self.counts.invariant_failures.load(Ordering::Relaxed),
+                )
+            }
+        }
+
+        impl Default for ConsoleLayer {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+
+        struct TallyVisitor<'a> {
+            counts: &'a Counts,
+        }
+
+        impl Visit for TallyVisitor<'_> {
+            fn record_debug(&mut self, _field: &Field, _value: &dyn std::fmt::Debug) {}
+
+            fn record_bool(&mut self, field: &Field, value: bool) {
+                if field.name() != "holds" {
+                    return;
+                }
+                if value {
+                    self.counts.invariant_passes.fetch_add(1, Ordering::Relaxed);
+                } else {
+                    self.counts.invariant_failures.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+        }
+
+        impl<S: tracing::Subscriber> Layer<S> for ConsoleLayer {
+            fn on_event(&self, event: &tracing::Event<'_>, _ctx: Context<'_, S>) {
+                // `tracing` gives every event an auto-generated `name()` like
+                // `"event src/lib.rs:29"`, not the log message passed to
+                // `info!` — so we can't match on "operation applied" there.
+                // `op_applied` is the only call site that logs an `op`
+                // field, so its presence identifies the event instead.
+                if event.metadata().fields().field("op").is_some() {
+                    self.counts.steps.fetch_add(1, Ordering::Relaxed);
+                }
+                event.record(&mut TallyVisitor {
+                    counts: &self.counts,
+                });
+            }
+        }
+    }
+
+    #[cfg(feature = "console")]
+    pub mod ndjson {
+        //! Writes the event stream as newline-delimited JSON, one object
+        //! per event holding its target, level, and fields (each field
+        //! value as its `Debug` string). `Replay` and `Shrink` do not read
+        //! this format back.
+
+        use std::fs::File;
+        use std::io::Write;
+        use std::sync::Mutex;
+
+        use serde_json::{json, Map, Value};
+        use tracing::field::{Field, Visit};
+        use tracing_subscriber::layer::Context;
+        use tracing_subscriber::Layer;
+
+        pub struct NdjsonFileLayer {
+            file: Mutex<File>,
+        }
+
+        impl NdjsonFileLayer {
+            pub fn create(path: &str) -> std::io::Result<Self> {
+                Ok(Self {
+                    file: Mutex::new(File::create(path)?),
+                })
+            }
+        }
+
+        #[derive(Default)]
+        struct FieldVisitor {
+            fields: Map<String, Value>,
+        }
+
+        impl Visit for FieldVisitor {
+            fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+                self.fields
+                    .insert(field.name().to_string(), json!(format!("{value:?}")));
+            }
+        }
+
+        impl<S: tracing::Subscriber> Layer<S> for NdjsonFileLayer {
+            fn on_event(&self, event: &tracing::Event<'_>, _ctx: Context<'_, S>) {
+                let mut visitor = FieldVisitor::default();
+                event.record(&mut visitor);
+
+                let line = json!({
+                    "target": event.metadata().target(),
+                    "level": event.metadata().level().as_str(),
+                    "fields": visitor.fields,
+                })
+                .to_string();
+
+                if let Ok(mut file) = self.file.lock() {
+                    let _ = writeln!(file, "{line}");
+                }
+            }
+        }
+    }
+}
+
+pub mod repro {
+    //! A captured failing run, serialized so it can be replayed later
+    //! without re-exploring the seed's full schedule from scratch.
+
+    use serde::{Deserialize, Serialize};
+
+    /// Everything needed to deterministically redrive a run that ended in
+    /// `InvariantViolated`: which system it was, the seed and step budget
+    /// it used, and the exact sequence of operation indices it chose.
+    #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+    pub struct Repro {
+        pub seed: u64,
+        pub budget: Option<u64>,
+        pub system: String,
+        pub schedule: Vec<usize>,
+    }
+
+    impl Repro {
+        pub fn write_to(&self, path: &str) -> std::io::Result<()> {
+            let json = serde_json::to_string_pretty(self).expect("serialize repro");
+            std::fs::write(path, json)
+        }
+
+        pub fn read_from(path: &str) -> std::io::Result<Self> {
+            let raw = std::fs::read_to_string(path)?;
+            Ok(serde_json::from_str(&raw).expect("deserialize repro"))
+        }
+    }
+}
+
+pub mod engine {
+    //! A lower-level simulation engine for systems that model their own
+    //! lifecycle (init/apply/crash/restore/observe) rather than being
+    //! assembled from [`crate::system::SystemModel`] operations.
+
+    use crate::rng::SplitMix64;
+
+    /// Controls whether, and how often, [`simulate`] injects a crash and
+    /// restore mid-run rather than only once at the very end of a schedule.
+    #[derive(Clone, Debug, Default)]
+    pub enum FaultPolicy {
+        /// Never inject a crash mid-run (the final crash/restore still
+        /// always happens). This is the default, so existing callers that
+        /// construct an `EngineConfig` without thinking about faults keep
+        /// their prior, deterministic behavior.
+        #[default]
+        Never,
+        /// After each applied operation, inject a crash/restore with this
+        /// probability, drawn from the run's seeded PRNG so the choice of
+        /// which step boundaries get a fault is itself reproducible.
+        Probabilistic { probability: f64 },
+    }
+
+    impl FaultPolicy {
+        fn should_inject(&self, rng: &mut SplitMix64) -> bool {
+            match self {
+                FaultPolicy::Never => false,
+                FaultPolicy::Probabilistic { probability } => {
+                    let draw = rng.next_u64() as f64 / u64::MAX as f64;
+                    draw < *probability
+                }
+            }
+        }
+    }
+
+    /// Per-run configuration handed to [`simulate`].
+    #[derive(Clone, Debug, Default)]
+    pub struct EngineConfig<C> {
+        pub seed: u64,
+        pub budget: Option<u64>,
+        pub system_config: C,
+        pub fault_policy: FaultPolicy,
+    }
+
+    /// A system that can be driven end-to-end by the engine, including the
+    /// crash/restore lifecycle used to catch persistence bugs.
+    pub trait SimulationSystem: Sized {
+        type Config;
+        type Operation: Clone;
+        type Observation;
+        type PersistedState;
+
+        fn init(config: Self::Config) -> Self;
+        fn apply(&mut self, op: Self::Operation);
+        fn crash(self) -> Self::PersistedState;
+        fn restore(state: Self::PersistedState) -> Self;
+        fn observe(&self) -> Self::Observation;
+    }
+
+    /// A single entry in a run's trace: either an applied operation or a
+    /// fault the scheduler injected at that step boundary. Keeping these as
+    /// distinct variants (rather than folding the fault into `Applied`)
+    /// means a recorded trace can tell the two apart when replayed.
+    pub enum SimulationStep<S: SimulationSystem> {
+        Applied {
+            op: S::Operation,
+            observation: S::Observation,
+        },
+        FaultInjected {
+            observation: S::Observation,
+        },
+    }
+
+    impl<S: SimulationSystem> Clone for SimulationStep<S>
+    where
+        S::Operation: Clone,
+        S::Observation: Clone,
+    {
+        fn clone(&self) -> Self {
+            match self {
+                SimulationStep::Applied { op, observation } => SimulationStep::Applied {
+                    op: op.clone(),
+                    observation: observation.clone(),
+                },
+                SimulationStep::FaultInjected { observation } => SimulationStep::FaultInjected {
+                    observation: observation.clone(),
+                },
+            }
+        }
+    }
+
+    impl<S: SimulationSystem> std::fmt::Debug for SimulationStep<S>
+    where
+        S::Operation: std::fmt::Debug,
+        S::Observation: std::fmt::Debug,
+    {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            match self {
+                SimulationStep::Applied { op, observation } => f
+                    .debug_struct("Applied")
+                    .field("op", op)
+                    .field("observation", observation)
+                    .finish(),
+                SimulationStep::FaultInjected { observation } => f
+                    .debug_struct("FaultInjected")
+                    .field("observation", observation)
+                    .finish(),
+            }
+        }
+    }
+
+    impl<S: SimulationSystem> SimulationStep<S> {
+        /// The observation taken immediately after this step, whether it
+        /// was an applied operation or an injected fault.
+        pub fn observation(&self) -> &S::Observation {
+            match self {
+                SimulationStep::Applied { observation, .. } => observation,
+                SimulationStep::FaultInjected { observation } => observation,
+            }
+        }
+    }
+
+    /// How a run ended: either it completed, or a fault's restored
+    /// observation diverged from what was observed right before the crash
+    /// (a lost-write / persistence bug), kept distinct from
+    /// [`crate::simulation::SimulationStatus::InvariantViolated`] since it's
+    /// the engine's own restore-consistency check, not a user invariant.
+    pub enum SimulationStatus<S: SimulationSystem> {
+        Completed,
+        PostRestoreDivergence {
+            at_step: usize,
+            expected: S::Observation,
+            actual: S::Observation,
+        },
+    }
+
+    impl<S: SimulationSystem> std::fmt::Debug for SimulationStatus<S>
+    where
+        S::Observation: std::fmt::Debug,
+    {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            match self {
+                SimulationStatus::Completed => write!(f, "Completed"),
+                SimulationStatus::PostRestoreDivergence {
+                    at_step,
+                    expected,
+                    actual,
+                } => f
+                    .debug_struct("PostRestoreDivergence")
+                    .field("at_step", at_step)
+                    .field("expected", expected)
+                    .field("actual", actual)
+                    .finish(),
+            }
+        }
+    }
+
+    /// The result of driving a [`SimulationSystem`] through a schedule of
+    /// operations (with any injected faults interleaved) and then crashing
+    /// and restoring it one final time.
+    pub struct SimulationOutcome<S: SimulationSystem> {
+        pub steps: Vec<SimulationStep<S>>,
+        pub status: SimulationStatus<S>,
+        pub crash_state: S::PersistedState,
+        pub post_crash_observation: S::Observation,
+    }
+
+    impl<S: SimulationSystem> std::fmt::Debug for SimulationOutcome<S>
+    where
+        S::Operation: std::fmt::Debug,
+        S::Observation: std::fmt::Debug,
+        S::PersistedState: std::fmt::Debug,
+    {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.debug_struct("SimulationOutcome")
+                .field("steps", &self.steps)
+                .field("status", &self.status)
+                .field("crash_state", &self.crash_state)
+                .field("post_crash_observation", &self.post_crash_observation)
+                .finish()
+        }
+    }
+
+    /// Drive `operations` through a freshly initialized `S`, capping the
+    /// number of applied operations at `config.budget` when set. Between
+    /// applied operations, `config.fault_policy` may nondeterministically
+    /// (but reproducibly, via `config.seed`) inject a crash/restore; if the
+    /// restored observation doesn't match what was observed right before
+    /// the crash, the run stops with `PostRestoreDivergence`. Either way the
+    /// system is crashed and restored one final time once the run stops
+    /// (diverged, or schedule or budget exhausted), exercising the
+    /// persistence path even when no faults were injected along the way.
+    pub fn simulate<S>(
+        config: EngineConfig<S::Config>,
+        operations: &[S::Operation],
+    ) -> SimulationOutcome<S>
+    where
+        S: SimulationSystem,
+        S::PersistedState: Clone,
+        S::Observation: Clone + PartialEq,
+        S::Operation: std::fmt::Debug,
+    {
+        let span = crate::trace::run_span("engine::simulate", config.seed);
+        let _enter = span.enter();
+
+        let mut system = S::init(config.system_config);
+        let limit = config
+            .budget
+            .map(|budget| (budget as usize).min(operations.len()))
+            .unwrap_or(operations.len());
+
+        let mut rng = SplitMix64::new(config.seed);
+        let mut steps = Vec::with_capacity(limit);
+        let mut status = SimulationStatus::Completed;
+
+        for (step_index, op) in operations.iter().take(limit).cloned().enumerate() {
+            system.apply(op.clone());
+            let observation = system.observe();
+            // There's no separate op-index catalog in this engine the way
+            // `SystemModel` has one — operations are simply applied in
+            // sequence — so `op_index` is just this step's position too;
+            // what was missing was the operation's own description.
+            crate::trace::op_applied(step_index, step_index, &format!("{op:?}"));
+            steps.push(SimulationStep::Applied {
+                op,
+                observation: observation.clone(),
+            });
+
+            if config.fault_policy.should_inject(&mut rng) {
+                system = S::restore(system.crash());
+                let restored_observation = system.observe();
+                crate::trace::fault_injected(step_index);
+                steps.push(SimulationStep::FaultInjected {
+                    observation: restored_observation.clone(),
+                });
+
+                if restored_observation != observation {
+                    status = SimulationStatus::PostRestoreDivergence {
+                        at_step: step_index,
+                        expected: observation,
+                        actual: restored_observation,
+                    };
+                    break;
+                }
+            }
+        }
+
+        let crash_state = system.crash();
+        let restored = S::restore(crash_state.clone());
+        let post_crash_observation = restored.observe();
+
+        match status {
+            SimulationStatus::Completed => crate::trace::outcome_completed("engine::simulate"),
+            SimulationStatus::PostRestoreDivergence { .. } => {
+                crate::trace::outcome_invariant_violated("engine::simulate", 1)
+            }
+        }
+
+        SimulationOutcome {
+            steps,
+            status,
+            crash_state,
+            post_crash_observation,
+        }
+    }
+}
+
+pub mod shrink {
+    //! Delta-debugging (ddmin) minimization, the same technique corpus-
+    //! minimizing fuzzers apply to shrink a crashing input down to a
+    //! 1-minimal repro while preserving the same failure.
+
+    /// Shrink `input` to a locally 1-minimal subsequence for which
+    /// `is_interesting` still returns `true`, following Zeller &
+    /// Hildebrandt's ddmin algorithm: at each granularity, first test each
+    /// chunk alone (and restart on it at granularity 2 if it's still
+    /// interesting on its own), then test each complement (`current` minus
+    /// one chunk) before doubling the granularity into smaller chunks.
+    pub fn ddmin<T, F>(mut current: Vec<T>, is_interesting: F) -> Vec<T>
+    where
+        T: Clone,
+        F: Fn(&[T]) -> bool,
+    {
+        let mut granularity = 2usize;
+
+        'shrink: loop {
+            if current.len() < 2 {
+                break;
+            }
+
+            let chunk_size = (current.len() + granularity - 1) / granularity;
+
+            let mut start = 0;
+            while start < current.len() {
+                let end = (start + chunk_size).min(current.len());
+
+                let subset: Vec<T> = current[start..end].to_vec();
+                if !subset.is_empty() && subset.len() < current.len() && is_interesting(&subset) {
+                    current = subset;
+                    granularity = 2;
+                    continue 'shrink;
+                }
+
+                start += chunk_size;
+            }
+
+            let mut start = 0;
+            while start < current.len() {
+                let end = (start + chunk_size).min(current.len());
+
+                let complement: Vec<T> = current[..start]
+                    .iter()
+                    .chain(current[end..].iter())
+                    .cloned()
+                    .collect();
+
+                if !complement.is_empty() && is_interesting(&complement) {
+                    current = complement;
+                    granularity = (granularity.saturating_sub(1)).max(2);
+                    continue 'shrink;
+                }
+
+                start += chunk_size;
+            }
+
+            if granularity >= current.len() {
+                break;
+            }
+            granularity = (granularity * 2).min(current.len());
+        }
+
+        current
+    }
+}
+
 pub mod invariant {
     use serde::Serialize;
 
+    /// How seriously a violated invariant should be taken: a `Warn` is
+    /// recorded but lets the run continue, an `Error` halts it.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+    #[serde(rename_all = "snake_case")]
+    pub enum Severity {
+        Warn,
+        Error,
+    }
+
     #[derive(Debug, Serialize)]
     pub struct InvariantResult {
         pub name: &'static str,
+        pub severity: Severity,
         pub holds: bool,
+        pub message: Option<String>,
     }
 
     #[derive(Clone)]
     pub struct Invariant<O> {
         pub name: &'static str,
-        check: fn(&O) -> bool,
+        pub severity: Severity,
+        check: fn(&O) -> Option<String>,
+        reads: &'static [&'static str],
     }
 
     impl<O> Invariant<O> {
-        pub fn new(name: &'static str, check: fn(&O) -> bool) -> Self {
-            Self { name, check }
+        /// `check` returns `None` when the invariant holds, or
+        /// `Some(message)` describing why it doesn't. The invariant has no
+        /// declared `reads` by default, so it's re-evaluated on every step;
+        /// call [`Invariant::with_reads`] to opt into dependency tracking.
+        pub fn new(name: &'static str, severity: Severity, check: fn(&O) -> Option<String>) -> Self {
+            Self {
+                name,
+                severity,
+                check,
+                reads: &[],
+            }
+        }
+
+        /// Declares the named observation fields this invariant's `check`
+        /// actually reads, so [`Dirty::subscribes`] can skip re-evaluating
+        /// it on steps that couldn't have changed any of them.
+        pub fn with_reads(mut self, reads: &'static [&'static str]) -> Self {
+            self.reads = reads;
+            self
+        }
+
+        pub fn reads(&self) -> &'static [&'static str] {
+            self.reads
         }
 
         pub fn evaluate(&self, observation: &O) -> InvariantResult {
+            let message = (self.check)(observation);
             InvariantResult {
                 name: self.name,
-                holds: (self.check)(observation),
+                severity: self.severity,
+                holds: message.is_none(),
+                message,
+            }
+        }
+    }
+
+    /// Which observation fields a step could have changed, used to decide
+    /// which invariants are worth re-evaluating after it. `All` is the safe
+    /// fallback: an invariant with no declared `reads`, or a step whose
+    /// operation declared no `writes`, is always assumed to be affected.
+    #[derive(Clone, Debug)]
+    pub enum Dirty {
+        All,
+        Keys(std::collections::HashSet<&'static str>),
+    }
+
+    impl Dirty {
+        /// From the set of fields an applied operation declared it writes:
+        /// an empty write-set means "unknown", which falls back to `All`
+        /// rather than silently skipping every invariant.
+        pub fn from_writes(writes: &'static [&'static str]) -> Self {
+            if writes.is_empty() {
+                Dirty::All
+            } else {
+                Dirty::Keys(writes.iter().copied().collect())
+            }
+        }
+
+        /// Whether an invariant declaring `reads` should be re-evaluated
+        /// given these dirtied keys.
+        pub fn subscribes(&self, reads: &[&'static str]) -> bool {
+            match self {
+                Dirty::All => true,
+                Dirty::Keys(keys) => reads.is_empty() || reads.iter().any(|key| keys.contains(key)),
             }
         }
     }
 }
 
 pub mod operation {
+    use crate::invariant::Dirty;
+
     pub struct Operation<S> {
         pub name: &'static str,
         apply: Box<dyn Fn(&mut S) + Send + Sync>,
+        writes: &'static [&'static str],
     }
 
     impl<S> Operation<S> {
+        /// The operation has no declared `writes` by default, so the engine
+        /// conservatively treats every invariant as possibly affected; call
+        /// [`Operation::with_writes`] to opt into dependency tracking.
         pub fn new(name: &'static str, apply: impl Fn(&mut S) + Send + Sync + 'static) -> Self {
             Self {
                 name,
                 apply: Box::new(apply),
+                writes: &[],
             }
         }
 
+        /// Declares the named observation fields this operation's `apply`
+        /// can change, so the engine only re-evaluates invariants
+        /// subscribed to one of them after this operation runs.
+        pub fn with_writes(mut self, writes: &'static [&'static str]) -> Self {
+            self.writes = writes;
+            self
+        }
+
         pub fn apply(&self, state: &mut S) {
             (self.apply)(state);
         }
+
+        /// The [`Dirty`] set this operation's declared `writes` produce,
+        /// falling back to [`Dirty::All`] when none were declared.
+        pub fn dirtied(&self) -> Dirty {
+            Dirty::from_writes(self.writes)
+        }
     }
 }
 
@@ -128,24 +780,61 @@ pub mod system {
     }
 }
 
+pub mod rng {
+    //! A minimal splitmix64 PRNG. No external RNG crate is needed for the
+    //! engine's purposes: it only has to be deterministic and reproducible
+    //! from a `u64` seed, not cryptographically strong.
+
+    #[derive(Clone, Debug)]
+    pub struct SplitMix64 {
+        state: u64,
+    }
+
+    impl SplitMix64 {
+        pub fn new(seed: u64) -> Self {
+            Self { state: seed }
+        }
+
+        pub fn next_u64(&mut self) -> u64 {
+            self.state = self.state.wrapping_add(0x9E3779B97F4A7C15);
+            let mut z = self.state;
+            z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+            z ^ (z >> 31)
+        }
+
+        /// A uniformly distributed index in `[0, len)`.
+        pub fn next_index(&mut self, len: usize) -> usize {
+            (self.next_u64() % len as u64) as usize
+        }
+    }
+}
+
 pub mod simulation {
     use serde::Serialize;
     use serde_json::Value;
 
     use crate::{
-        invariant::{Invariant, InvariantResult},
+        invariant::{Dirty, Invariant, InvariantResult, Severity},
+        rng::SplitMix64,
         system::{Observation, SystemModel},
     };
 
     #[derive(Debug, Serialize, Clone)]
     pub struct SimulationStep {
         pub op: &'static str,
-        pub iteration: usize,
+        pub op_index: usize,
+        /// This step's position in the run (0-based), not a round number:
+        /// `run` takes `rounds * operations.len()` such steps in total.
+        pub step_index: usize,
+        pub warnings: Vec<InvariantFailure>,
     }
 
-    #[derive(Debug, Serialize)]
+    #[derive(Debug, Serialize, Clone)]
     pub struct InvariantFailure {
         pub invariant: &'static str,
+        pub severity: Severity,
+        pub message: Option<String>,
         pub step: Option<SimulationStep>,
     }
 
@@ -153,7 +842,7 @@ pub mod simulation {
     #[serde(tag = "status", rename_all = "snake_case")]
     pub enum SimulationStatus {
         Completed,
-        InvariantViolated(InvariantFailure),
+        InvariantViolated(Vec<InvariantFailure>),
     }
 
     #[derive(Debug, Serialize)]
@@ -161,6 +850,9 @@ pub mod simulation {
         pub system: String,
         pub steps: Vec<SimulationStep>,
         pub status: SimulationStatus,
+        /// Every `Warn`-severity failure observed during the run, in the
+        /// order it was recorded (the initial, pre-run checkpoint included).
+        pub warnings: Vec<InvariantFailure>,
     }
 
     impl SimulationOutcome {
@@ -171,73 +863,256 @@ pub mod simulation {
 
     pub struct Simulation<S, O> {
         model: SystemModel<S, O>,
+        seed: u64,
     }
 
     impl<S: Clone, O> Simulation<S, O> {
+        /// Build a simulation with a fixed seed of `0`, for callers that
+        /// don't care about exploring different interleavings.
         pub fn new(model: SystemModel<S, O>) -> Self {
-            Self { model }
+            Self::with_seed(model, 0)
+        }
+
+        /// Build a simulation whose scheduler is driven by `seed`: the same
+        /// seed against the same model always picks the same sequence of
+        /// operations.
+        pub fn with_seed(model: SystemModel<S, O>, seed: u64) -> Self {
+            Self { model, seed }
         }
 
+        /// Run `rounds * operations.len()` steps, each step sampling the
+        /// next operation to apply from a seeded PRNG rather than cycling
+        /// through `model.operations` in fixed order.
         pub fn run(&self, rounds: usize) -> SimulationOutcome {
+            let total_steps = rounds * self.model.operations.len();
+            self.drive(self.seed, total_steps)
+        }
+
+        /// Run the model with its scheduler re-seeded from `seed`, capped
+        /// at `budget` total steps (one pass over `model.operations` when
+        /// `None`) — useful for exploring several seeds against the same
+        /// model without reconstructing a `Simulation` for each one.
+        pub fn run_with_seed(&self, seed: u64, budget: Option<u64>) -> SimulationOutcome {
+            let total_steps = budget
+                .map(|budget| budget as usize)
+                .unwrap_or(self.model.operations.len());
+            self.drive(seed, total_steps)
+        }
+
+        fn drive(&self, seed: u64, total_steps: usize) -> SimulationOutcome {
+            let span = crate::trace::run_span(&self.model.name, seed);
+            let _enter = span.enter();
+
             let mut state = (self.model.init)();
             let mut steps = Vec::new();
 
-            if let Some(failure) =
-                check_invariants(None, &self.model.invariants, &self.model.observe, &state)
-            {
+            let initial = check_invariants(
+                None,
+                &self.model.invariants,
+                &self.model.observe,
+                &state,
+                Dirty::All,
+            );
+            if !initial.errors.is_empty() {
+                crate::trace::outcome_invariant_violated(&self.model.name, initial.errors.len());
                 return SimulationOutcome {
                     system: self.model.name.clone(),
                     steps,
-                    status: SimulationStatus::InvariantViolated(failure),
+                    status: SimulationStatus::InvariantViolated(initial.errors),
+                    warnings: initial.warnings,
                 };
             }
+            let mut warnings = initial.warnings;
 
-            for iteration in 0..rounds {
-                for op in &self.model.operations {
-                    op.apply(&mut state);
-                    let step = SimulationStep {
-                        op: op.name,
-                        iteration,
+            if self.model.operations.is_empty() || total_steps == 0 {
+                crate::trace::outcome_completed(&self.model.name);
+                return SimulationOutcome {
+                    system: self.model.name.clone(),
+                    steps,
+                    status: SimulationStatus::Completed,
+                    warnings,
+                };
+            }
+
+            let mut rng = SplitMix64::new(seed);
+
+            for step_index in 0..total_steps {
+                let op_index = rng.next_index(self.model.operations.len());
+                let op = &self.model.operations[op_index];
+                op.apply(&mut state);
+                crate::trace::op_applied(step_index, op_index, op.name);
+
+                let mut step = SimulationStep {
+                    op: op.name,
+                    op_index,
+                    step_index,
+                    warnings: Vec::new(),
+                };
+                let checked = check_invariants(
+                    Some(step.clone()),
+                    &self.model.invariants,
+                    &self.model.observe,
+                    &state,
+                    op.dirtied(),
+                );
+                step.warnings = checked.warnings.clone();
+                warnings.extend(checked.warnings);
+                if !checked.errors.is_empty() {
+                    // The violating step still belongs in `steps`: a
+                    // `Repro`'s schedule is built from `op_index`, and
+                    // dropping this step here would silently omit the one
+                    // operation that actually needs replaying.
+                    steps.push(step);
+                    crate::trace::outcome_invariant_violated(&self.model.name, checked.errors.len());
+                    return SimulationOutcome {
+                        system: self.model.name.clone(),
+                        steps,
+                        status: SimulationStatus::InvariantViolated(checked.errors),
+                        warnings,
                     };
-                    if let Some(failure) = check_invariants(
-                        Some(step.clone()),
-                        &self.model.invariants,
-                        &self.model.observe,
-                        &state,
-                    ) {
-                        return SimulationOutcome {
-                            system: self.model.name.clone(),
-                            steps,
-                            status: SimulationStatus::InvariantViolated(failure),
-                        };
-                    }
+                }
+
+                steps.push(step);
+            }
+
+            crate::trace::outcome_completed(&self.model.name);
+            SimulationOutcome {
+                system: self.model.name.clone(),
+                steps,
+                status: SimulationStatus::Completed,
+                warnings,
+            }
+        }
+
+        /// Re-drive the model by applying exactly the operation indices in
+        /// `schedule`, in order, with no randomness involved. This is how a
+        /// captured [`crate::repro::Repro`] is replayed: the exact recorded
+        /// steps are re-applied rather than reseeding and hoping the same
+        /// steps come out. It returns the resulting `SimulationOutcome` and
+        /// does not itself compare it against the original run's outcome.
+        pub fn replay(&self, schedule: &[usize]) -> SimulationOutcome {
+            let span = crate::trace::run_span(&self.model.name, self.seed);
+            let _enter = span.enter();
+
+            let mut state = (self.model.init)();
+            let mut steps = Vec::new();
+
+            let initial = check_invariants(
+                None,
+                &self.model.invariants,
+                &self.model.observe,
+                &state,
+                Dirty::All,
+            );
+            if !initial.errors.is_empty() {
+                crate::trace::outcome_invariant_violated(&self.model.name, initial.errors.len());
+                return SimulationOutcome {
+                    system: self.model.name.clone(),
+                    steps,
+                    status: SimulationStatus::InvariantViolated(initial.errors),
+                    warnings: initial.warnings,
+                };
+            }
+            let mut warnings = initial.warnings;
+
+            for (step_index, &op_index) in schedule.iter().enumerate() {
+                let op = &self.model.operations[op_index];
+                op.apply(&mut state);
+                crate::trace::op_applied(step_index, op_index, op.name);
+
+                let mut step = SimulationStep {
+                    op: op.name,
+                    op_index,
+                    step_index,
+                    warnings: Vec::new(),
+                };
+                let checked = check_invariants(
+                    Some(step.clone()),
+                    &self.model.invariants,
+                    &self.model.observe,
+                    &state,
+                    op.dirtied(),
+                );
+                step.warnings = checked.warnings.clone();
+                warnings.extend(checked.warnings);
+                if !checked.errors.is_empty() {
                     steps.push(step);
+                    crate::trace::outcome_invariant_violated(&self.model.name, checked.errors.len());
+                    return SimulationOutcome {
+                        system: self.model.name.clone(),
+                        steps,
+                        status: SimulationStatus::InvariantViolated(checked.errors),
+                        warnings,
+                    };
                 }
+
+                steps.push(step);
             }
 
+            crate::trace::outcome_completed(&self.model.name);
             SimulationOutcome {
                 system: self.model.name.clone(),
                 steps,
                 status: SimulationStatus::Completed,
+                warnings,
             }
         }
     }
 
+    /// All invariant failures observed at one checkpoint, split by
+    /// severity: `warnings` are recorded but let the run continue,
+    /// `errors` halt it.
+    struct CheckedInvariants {
+        warnings: Vec<InvariantFailure>,
+        errors: Vec<InvariantFailure>,
+    }
+
     fn check_invariants<S: Clone, O>(
         step: Option<SimulationStep>,
         invariants: &[Invariant<O>],
         observer: &Observation<S, O>,
         state: &S,
-    ) -> Option<InvariantFailure> {
+        dirtied: Dirty,
+    ) -> CheckedInvariants {
         let observation = observer.view(state);
+        let mut warnings = Vec::new();
+        let mut errors = Vec::new();
+
+        for invariant in invariants {
+            if !dirtied.subscribes(invariant.reads()) {
+                continue;
+            }
+
+            let InvariantResult {
+                name,
+                severity,
+                holds,
+                message,
+            } = invariant.evaluate(&observation);
 
-        invariants
-            .iter()
-            .map(|invariant| invariant.evaluate(&observation))
-            .find(|result| !result.holds)
-            .map(|InvariantResult { name, .. }| InvariantFailure {
+            let severity_label = match severity {
+                Severity::Warn => "warn",
+                Severity::Error => "error",
+            };
+            crate::trace::invariant_checked(name, severity_label, holds);
+
+            if holds {
+                continue;
+            }
+
+            let failure = InvariantFailure {
                 invariant: name,
-                step,
-            })
+                severity,
+                message,
+                step: step.clone(),
+            };
+
+            match severity {
+                Severity::Warn => warnings.push(failure),
+                Severity::Error => errors.push(failure),
+            }
+        }
+
+        CheckedInvariants { warnings, errors }
     }
 }
@@ -0,0 +1,34 @@
+use nomercy_core::prelude::{Invariant, Operation, Severity, Simulation, SimulationStatus, SystemModel};
+
+fn build_counter() -> SystemModel<i64, i64> {
+    SystemModel::new("counter", || 0)
+        .operation(Operation::new("increment", |state: &mut i64| *state += 1))
+        .invariant(Invariant::new(
+            "below_three",
+            Severity::Error,
+            |observation: &i64| {
+                if *observation < 3 {
+                    None
+                } else {
+                    Some("counter reached 3".to_string())
+                }
+            },
+        ))
+}
+
+#[test]
+fn repro_round_trip_is_deterministic() {
+    let simulation = Simulation::with_seed(build_counter(), 7);
+    let outcome = simulation.run(5);
+
+    let schedule: Vec<usize> = outcome.steps.iter().map(|step| step.op_index).collect();
+    assert!(
+        matches!(outcome.status, SimulationStatus::InvariantViolated(_)),
+        "the invariant should have been violated before the run completed"
+    );
+    assert!(!schedule.is_empty(), "the violating run should have applied at least one operation");
+
+    let replayed = simulation.replay(&schedule);
+
+    assert_eq!(outcome.to_json(), replayed.to_json());
+}
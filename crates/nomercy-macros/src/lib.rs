@@ -1,31 +1,231 @@
+use std::collections::HashSet;
+
 use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
 use quote::quote;
-use syn::{parse_macro_input, Item};
+use syn::{parse_macro_input, Attribute, Item, ItemFn, ItemMod, LitStr};
 
 fn passthrough(item: Item) -> TokenStream {
     quote!(#item).into()
 }
 
+/// Marks a function inside a `#[system]` module as an operation. Left as a
+/// passthrough here: `#[system]` reads this attribute off the surrounding
+/// module's items directly (before this macro ever runs on the nested fn),
+/// so by the time this expands there's nothing left to do but keep the fn
+/// body intact.
 #[proc_macro_attribute]
-pub fn system(_args: TokenStream, item: TokenStream) -> TokenStream {
+pub fn op(_args: TokenStream, item: TokenStream) -> TokenStream {
     let item = parse_macro_input!(item as Item);
     passthrough(item)
 }
 
+/// Marks the observation-projection function inside a `#[system]` module.
 #[proc_macro_attribute]
-pub fn op(_args: TokenStream, item: TokenStream) -> TokenStream {
+pub fn observe(_args: TokenStream, item: TokenStream) -> TokenStream {
     let item = parse_macro_input!(item as Item);
     passthrough(item)
 }
 
+/// Marks a `fn(&O) -> bool` inside a `#[system]` module as an invariant.
 #[proc_macro_attribute]
 pub fn invariant(_args: TokenStream, item: TokenStream) -> TokenStream {
     let item = parse_macro_input!(item as Item);
     passthrough(item)
 }
 
+/// Does this item carry a bare (or argument-taking) `#[path]` attribute?
+fn has_attr(attrs: &[Attribute], path: &str) -> bool {
+    attrs.iter().any(|attr| attr.path().is_ident(path))
+}
+
+/// Pulls an explicit `name = "..."` override out of `#[path(name = "...")]`.
+fn explicit_name(attrs: &[Attribute], path: &str) -> Option<String> {
+    attrs.iter().find_map(|attr| {
+        if !attr.path().is_ident(path) {
+            return None;
+        }
+        let mut found = None;
+        let _ = attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("name") {
+                let value: LitStr = meta.value()?.parse()?;
+                found = Some(value.value());
+            }
+            Ok(())
+        });
+        found
+    })
+}
+
+/// The `&StateTy` / `&ObservationTy` parameter and return types a
+/// `#[observe]` function's signature carries are how `#[system]` infers `S`
+/// and `O` for the `SystemModel<S, O>` it assembles, so no second place has
+/// to spell the state type out.
+fn referenced_type(ty: &syn::Type) -> Option<&syn::Type> {
+    match ty {
+        syn::Type::Reference(reference) => Some(&reference.elem),
+        _ => None,
+    }
+}
+
+/// Assembles a `SystemModel<S, O>` from a module annotated with `#[op]`,
+/// `#[observe]`, and `#[invariant]` functions, so a user writes plain Rust
+/// and gets a `build() -> SystemModel<S, O>` ready to hand to
+/// `Simulation::new` without writing the builder chain by hand.
+///
+/// This collects free functions out of a `#[system] mod { ... }` rather
+/// than methods off an annotated `impl`/`struct`. An impl-based DSL would
+/// need `#[op]`/`#[observe]`/`#[invariant]` to rewrite sibling methods from
+/// inside a macro invocation on just one of them, which attribute macros
+/// can't do — each expands independently and in isolation. Scoping
+/// everything inside one `mod` gives `#[system]` a single expansion with
+/// every annotated item in view, at the cost of taking `&`/`&mut State`
+/// parameters instead of `&self`/`&mut self`.
 #[proc_macro_attribute]
-pub fn observe(_args: TokenStream, item: TokenStream) -> TokenStream {
-    let item = parse_macro_input!(item as Item);
-    passthrough(item)
+pub fn system(_args: TokenStream, item: TokenStream) -> TokenStream {
+    let module = parse_macro_input!(item as ItemMod);
+
+    let Some((_, items)) = module.content.as_ref() else {
+        return syn::Error::new_spanned(
+            &module,
+            "#[system] requires a module with a body, e.g. `#[system] mod counter { ... }`",
+        )
+        .to_compile_error()
+        .into();
+    };
+
+    let mut errors: Vec<syn::Error> = Vec::new();
+    let mut seen_names: HashSet<String> = HashSet::new();
+
+    let mut observer: Option<&ItemFn> = None;
+    let mut ops: Vec<(String, &syn::Ident)> = Vec::new();
+    let mut invariants: Vec<(String, &syn::Ident)> = Vec::new();
+
+    for item in items {
+        let Item::Fn(item_fn) = item else { continue };
+
+        if has_attr(&item_fn.attrs, "observe") {
+            if observer.is_some() {
+                errors.push(syn::Error::new_spanned(
+                    &item_fn.sig.ident,
+                    "#[system] only supports one #[observe] function",
+                ));
+            }
+            observer = Some(item_fn);
+        }
+
+        if has_attr(&item_fn.attrs, "op") {
+            let name = explicit_name(&item_fn.attrs, "op").unwrap_or_else(|| item_fn.sig.ident.to_string());
+            if !seen_names.insert(name.clone()) {
+                errors.push(syn::Error::new_spanned(
+                    &item_fn.sig.ident,
+                    format!("duplicate operation/invariant name `{name}`"),
+                ));
+            }
+            ops.push((name, &item_fn.sig.ident));
+        }
+
+        if has_attr(&item_fn.attrs, "invariant") {
+            let name =
+                explicit_name(&item_fn.attrs, "invariant").unwrap_or_else(|| item_fn.sig.ident.to_string());
+            if !seen_names.insert(name.clone()) {
+                errors.push(syn::Error::new_spanned(
+                    &item_fn.sig.ident,
+                    format!("duplicate operation/invariant name `{name}`"),
+                ));
+            }
+            invariants.push((name, &item_fn.sig.ident));
+        }
+    }
+
+    let Some(observer) = observer else {
+        errors.push(syn::Error::new_spanned(
+            &module.ident,
+            "#[system] requires exactly one #[observe] function",
+        ));
+        return errors_to_tokens(errors);
+    };
+
+    if !errors.is_empty() {
+        return errors_to_tokens(errors);
+    }
+
+    let Some(syn::FnArg::Typed(state_arg)) = observer.sig.inputs.first() else {
+        return syn::Error::new_spanned(
+            &observer.sig,
+            "#[observe] function must take the system state by reference, e.g. `fn observe(state: &State) -> Observation`",
+        )
+        .to_compile_error()
+        .into();
+    };
+    let Some(state_ty) = referenced_type(&state_arg.ty) else {
+        return syn::Error::new_spanned(&state_arg.ty, "#[observe] must take its state by `&` reference")
+            .to_compile_error()
+            .into();
+    };
+    let syn::ReturnType::Type(_, obs_ty) = &observer.sig.output else {
+        return syn::Error::new_spanned(
+            &observer.sig,
+            "#[observe] function must return the system's Observation type",
+        )
+        .to_compile_error()
+        .into();
+    };
+
+    let observer_ident = &observer.sig.ident;
+    let (op_names, op_idents): (Vec<_>, Vec<_>) = ops.into_iter().unzip();
+    let (invariant_names, invariant_idents): (Vec<_>, Vec<_>) = invariants.into_iter().unzip();
+    let mod_ident = &module.ident;
+    let mod_vis = &module.vis;
+    let mod_attrs = &module.attrs;
+
+    let build_fn = quote! {
+        /// Assembles the `SystemModel` this module describes: `init` comes
+        /// from the state type's `Default` impl, `observe` is the module's
+        /// `#[observe]` function, and every `#[op]`/`#[invariant]` function
+        /// is registered under its (or its explicit `name = "..."`) name.
+        pub fn build() -> ::nomercy_core::prelude::SystemModel<#state_ty, #obs_ty> {
+            let mut model = ::nomercy_core::prelude::SystemModel::new(
+                stringify!(#mod_ident),
+                <#state_ty as ::std::default::Default>::default,
+            )
+            .with_observer(#observer_ident);
+
+            #(
+                model = model.operation(::nomercy_core::prelude::Operation::new(#op_names, #op_idents));
+            )*
+
+            #(
+                model = model.invariant(::nomercy_core::prelude::Invariant::new(
+                    #invariant_names,
+                    ::nomercy_core::prelude::Severity::Error,
+                    |observation: &#obs_ty| {
+                        if #invariant_idents(observation) {
+                            None
+                        } else {
+                            Some(::std::string::String::from(concat!("invariant `", #invariant_names, "` violated")))
+                        }
+                    },
+                ));
+            )*
+
+            model
+        }
+    };
+
+    let output = quote! {
+        #(#mod_attrs)*
+        #mod_vis mod #mod_ident {
+            #(#items)*
+
+            #build_fn
+        }
+    };
+
+    output.into()
+}
+
+fn errors_to_tokens(errors: Vec<syn::Error>) -> TokenStream {
+    let combined: TokenStream2 = errors.into_iter().map(|error| error.to_compile_error()).collect();
+    combined.into()
 }
@@ -0,0 +1,9 @@
+//! UI tests asserting that `#[system]` rejects malformed modules with a
+//! clear diagnostic instead of a confusing downstream type error.
+
+#[test]
+fn ui() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/missing_observer.rs");
+    t.compile_fail("tests/ui/duplicate_name.rs");
+}
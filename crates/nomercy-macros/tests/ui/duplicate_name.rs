@@ -0,0 +1,29 @@
+use nomercy_macros::{invariant, observe, op, system};
+
+#[system]
+mod counter {
+    #[derive(Clone, Default)]
+    pub struct State(pub i64);
+
+    #[op]
+    fn increment(state: &mut State) {
+        state.0 += 1;
+    }
+
+    #[observe]
+    fn observe(state: &State) -> i64 {
+        state.0
+    }
+
+    #[invariant(name = "increment")]
+    fn non_negative(observation: &i64) -> bool {
+        *observation >= 0
+    }
+
+    #[invariant(name = "increment")]
+    fn below_limit(observation: &i64) -> bool {
+        *observation < 1000
+    }
+}
+
+fn main() {}
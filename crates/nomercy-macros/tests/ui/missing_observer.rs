@@ -0,0 +1,14 @@
+use nomercy_macros::{op, system};
+
+#[system]
+mod counter {
+    #[derive(Clone, Default)]
+    pub struct State(pub i64);
+
+    #[op]
+    fn increment(state: &mut State) {
+        state.0 += 1;
+    }
+}
+
+fn main() {}